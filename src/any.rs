@@ -0,0 +1,130 @@
+use std::any::Any;
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use {Error, Id};
+
+/// Declares a stable identity for a concrete payload type so it can be routed
+/// through an [`AnyEventEmitter`](struct.AnyEventEmitter.html) without the
+/// caller naming the type at the call site.
+///
+/// Blanket-implemented for every `'static` type via [`std::any::type_name`],
+/// so payload types never need to implement this by hand.
+pub trait EventValue: Any {
+    fn type_id() -> &'static str where Self: Sized;
+}
+
+impl<T: Any> EventValue for T {
+    fn type_id() -> &'static str where Self: Sized {
+        ::std::any::type_name::<Self>()
+    }
+}
+
+struct AnyListener {
+    id: Id,
+    type_id: &'static str,
+    closure: Box<Fn(&Any)>
+}
+
+/// An [`EventEmitter`](struct.EventEmitter.html) variant whose listeners are
+/// not monomorphized to a single payload type. Each listener declares the
+/// concrete type it expects via `on::<T, _>`, and `emit::<T>` routes the
+/// payload only to listeners registered for that same `T`; listeners for any
+/// other type are silently skipped rather than panicking.
+pub struct AnyEventEmitter<E> where E: Eq + Hash {
+    next_id: Id,
+    map: HashMap<E, Vec<AnyListener>>
+}
+
+impl<E> AnyEventEmitter<E> where E: Eq + Hash {
+    pub fn new() -> Self {
+        Self {
+            next_id: Id::default(),
+            map: HashMap::new()
+        }
+    }
+
+    pub fn on<T, F>(&mut self, event: E, listener: F) -> Id
+    where T: EventValue + 'static, F: Fn(&T) + 'static {
+        let id = self.next_id;
+        let closure = Box::new(move |payload: &Any| {
+            if let Some(value) = payload.downcast_ref::<T>() {
+                listener(value);
+            }
+        });
+
+        let listeners = self.map.entry(event).or_insert(Vec::new());
+        listeners.push(AnyListener { id, type_id: <T as EventValue>::type_id(), closure });
+
+        self.next_id += 1;
+        id
+    }
+
+    pub fn off(&mut self, id: Id) -> Result<(), Error> {
+        for (_, listeners) in self.map.iter_mut() {
+            let position = listeners.iter().position(|x| x.id == id);
+
+            if let Some(idx) = position {
+                listeners.remove(idx);
+                return Ok(());
+            }
+        }
+
+        Err(Error::ListenerNotFound)
+    }
+
+    pub fn emit<T, B>(&self, event: &B, payload: T) -> Result<(), Error>
+    where T: EventValue + 'static, E: Borrow<B>, B: ?Sized + Hash + Eq {
+        match self.map.get(event) {
+            Some(listeners) => {
+                let type_id = <T as EventValue>::type_id();
+                let boxed: Box<Any> = Box::new(payload);
+
+                listeners.iter()
+                    .filter(|l| l.type_id == type_id)
+                    .for_each(|l| (l.closure)(boxed.as_ref()));
+
+                Ok(())
+            },
+            None => Err(Error::UnknownEvent)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_route_distinct_payload_types() {
+        use std::sync::mpsc::{channel, TryRecvError};
+
+        let (sx, rx) = channel();
+        let sx2 = sx.clone();
+
+        let mut emitter = AnyEventEmitter::new();
+        emitter.on("event", move |payload: &i32| sx.send(*payload).unwrap());
+        emitter.on("event", move |payload: &&str| sx2.send(payload.len() as i32).unwrap());
+
+        emitter.emit("event", 3).unwrap();
+        emitter.emit("event", "hi").unwrap();
+
+        assert_eq!(rx.recv(), Ok(3));
+        assert_eq!(rx.recv(), Ok(2));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    #[test]
+    fn can_unregister_listeners() {
+        let mut emitter = AnyEventEmitter::new();
+        let id = emitter.on("event", |_: &i32| {});
+        emitter.on("event", |_: &i32| {});
+
+        assert_eq!(emitter.map.get("event").unwrap().len(), 2);
+
+        assert!(emitter.off(id).is_ok());
+
+        assert_eq!(emitter.map.get("event").unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,120 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use {Error, Id};
+
+struct TryListener<P, L> {
+    id: Id,
+    closure: Box<Fn(P) -> Result<(), L>>
+}
+
+impl<P, L> TryListener<P, L> {
+    fn new<F>(id: Id, f: F) -> Self where F: Fn(P) -> Result<(), L> + 'static {
+        Self {
+            id,
+            closure: Box::new(f)
+        }
+    }
+}
+
+/// An [`EventEmitter`](struct.EventEmitter.html) variant whose listeners can report
+/// failure. [`try_emit`](#method.try_emit) invokes every listener for the event,
+/// continuing past failures instead of short-circuiting, and returns the
+/// `(listener_id, error)` pair of each one that failed.
+pub struct TryEventEmitter<E, P, L> where E: Eq + Hash, P: Clone {
+    next_id: Id,
+    map: HashMap<E, Vec<TryListener<P, L>>>
+}
+
+impl<E, P, L> TryEventEmitter<E, P, L> where E: Eq + Hash, P: Clone {
+    pub fn new() -> Self {
+        Self {
+            next_id: Id::default(),
+            map: HashMap::new()
+        }
+    }
+
+    pub fn on_try<F>(&mut self, event: E, listener: F) -> Id
+    where F: Fn(P) -> Result<(), L> + 'static {
+        let id = self.next_id;
+        let listeners = self.map.entry(event).or_insert(Vec::new());
+
+        listeners.push(TryListener::new(self.next_id, listener));
+
+        self.next_id += 1;
+        id
+    }
+
+    pub fn off(&mut self, id: Id) -> Result<(), Error> {
+        for (_, listeners) in self.map.iter_mut() {
+            let position = listeners.iter().position(|x| x.id == id);
+
+            if let Some(idx) = position {
+                listeners.remove(idx);
+                return Ok(());
+            }
+        }
+
+        Err(Error::ListenerNotFound)
+    }
+
+    pub fn try_emit<B>(&self, event: &B, payload: P) -> Result<(), Vec<(Id, L)>>
+    where E: Borrow<B>, B: ?Sized + Hash + Eq {
+        let errors: Vec<(Id, L)> = match self.map.get(event) {
+            Some(listeners) => listeners.iter()
+                .filter_map(|l| match (l.closure)(payload.clone()) {
+                    Ok(()) => None,
+                    Err(e) => Some((l.id, e))
+                })
+                .collect(),
+            None => Vec::new()
+        };
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_emit_succeeds_when_every_listener_succeeds() {
+        let mut emitter = TryEventEmitter::new();
+        emitter.on_try("event", |_: i32| -> Result<(), &'static str> { Ok(()) });
+        emitter.on_try("event", |_: i32| -> Result<(), &'static str> { Ok(()) });
+
+        assert_eq!(emitter.try_emit("event", 1), Ok(()));
+    }
+
+    #[test]
+    fn try_emit_collects_errors_without_short_circuiting() {
+        let mut emitter = TryEventEmitter::new();
+        let id1 = emitter.on_try("event", |_: i32| Err("first"));
+        emitter.on_try("event", |_: i32| Ok(()));
+        let id3 = emitter.on_try("event", |_: i32| Err("third"));
+
+        assert_eq!(
+            emitter.try_emit("event", 1),
+            Err(vec![(id1, "first"), (id3, "third")])
+        );
+    }
+
+    #[test]
+    fn can_unregister_try_listeners() {
+        let mut emitter = TryEventEmitter::new();
+        let id = emitter.on_try("event", |_: i32| -> Result<(), &'static str> { Ok(()) });
+        emitter.on_try("event", |_: i32| -> Result<(), &'static str> { Ok(()) });
+
+        assert_eq!(emitter.map.get("event").unwrap().len(), 2);
+
+        assert!(emitter.off(id).is_ok());
+
+        assert_eq!(emitter.map.get("event").unwrap().len(), 1);
+    }
+}
@@ -1,8 +1,16 @@
 use std::borrow::Borrow;
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::hash::Hash;
+use std::rc::{Rc, Weak};
 
-type Id = u64;
+mod any;
+pub use any::{AnyEventEmitter, EventValue};
+
+mod fallible;
+pub use fallible::TryEventEmitter;
+
+pub(crate) type Id = u64;
 
 #[derive(Debug)]
 pub enum Error {
@@ -10,8 +18,118 @@ pub enum Error {
     UnknownEvent
 }
 
+#[derive(Debug, PartialEq)]
+pub enum TryRecvError {
+    Empty,
+    Lagged(u64)
+}
+
+struct RingBuffer<P> {
+    buf: Vec<Option<P>>,
+    cap: usize,
+    write: u64
+}
+
+impl<P: Clone> RingBuffer<P> {
+    fn new(cap: usize) -> Self {
+        // a zero-capacity ring would divide by zero on the very first push/read
+        let cap = cap.max(1);
+
+        Self {
+            buf: vec![None; cap],
+            cap,
+            write: 0
+        }
+    }
+
+    fn push(&mut self, payload: P) {
+        let idx = (self.write % self.cap as u64) as usize;
+        self.buf[idx] = Some(payload);
+        self.write += 1;
+    }
+}
+
+/// A pull-based handle for events emitted after it was created via
+/// [`EventEmitter::subscribe`](struct.EventEmitter.html#method.subscribe).
+///
+/// Events are stored in a bounded ring buffer, so a `Subscriber` never blocks
+/// the emitter. If the subscriber falls behind and the buffer wraps before it
+/// reads the oldest entries, `try_recv` reports how many events were skipped
+/// instead of silently dropping them.
+pub struct Subscriber<P> {
+    read: u64,
+    ring: Rc<RefCell<RingBuffer<P>>>
+}
+
+impl<P: Clone> Subscriber<P> {
+    /// Pulls the next event, if any is available.
+    ///
+    /// Returns `Err(TryRecvError::Lagged(n))` when `n` events were overwritten
+    /// before this subscriber could read them; the read cursor is advanced
+    /// past the gap so subsequent calls resume from the oldest event still in
+    /// the buffer.
+    pub fn try_recv(&mut self) -> Result<P, TryRecvError> {
+        let ring = RefCell::borrow(&self.ring);
+        let lag = ring.write.saturating_sub(self.read).saturating_sub(ring.cap as u64);
+
+        if lag > 0 {
+            self.read += lag;
+            return Err(TryRecvError::Lagged(lag));
+        }
+
+        if self.read >= ring.write {
+            return Err(TryRecvError::Empty);
+        }
+
+        let idx = (self.read % ring.cap as u64) as usize;
+        let payload = ring.buf[idx].clone().expect("ring slot within bounds must be populated");
+        self.read += 1;
+        Ok(payload)
+    }
+}
+
+impl<P: Clone> Iterator for Subscriber<P> {
+    type Item = P;
+
+    fn next(&mut self) -> Option<P> {
+        loop {
+            match self.try_recv() {
+                Ok(payload) => return Some(payload),
+                Err(TryRecvError::Lagged(_)) => continue,
+                Err(TryRecvError::Empty) => return None
+            }
+        }
+    }
+}
+
+struct ScopedEntry<P> {
+    closure: Weak<Fn(P)>
+}
+
+/// An RAII guard returned by [`EventEmitter::on_scoped`](struct.EventEmitter.html#method.on_scoped).
+/// The listener it guards fires for as long as this value is alive; dropping it
+/// makes the listener's slot reclaimable, swept out lazily on the next
+/// `on_scoped`/`emit` call for that event.
+pub struct Subscription<P> {
+    // kept alive only to hold the listener's strong count up; the emitter's
+    // `Weak` reference upgrades to `None` once this is dropped
+    #[allow(dead_code)]
+    closure: Rc<Fn(P)>
+}
+
+struct SubscriberEntry<P> {
+    id: Id,
+    ring: Weak<RefCell<RingBuffer<P>>>
+}
+
+enum Kind {
+    Normal,
+    Once
+}
+
 pub struct Listener<P> {
     id: Id,
+    kind: Kind,
     closure: Box<Fn(P)>
 }
 
@@ -19,24 +137,84 @@ impl<P> Listener<P> {
     pub fn new<F>(id: Id, f: F) -> Self where F: Fn(P) + 'static {
         Self {
             id,
+            kind: Kind::Normal,
+            closure: Box::new(f)
+        }
+    }
+
+    fn once<F>(id: Id, f: F) -> Self where F: Fn(P) + 'static {
+        Self {
+            id,
+            kind: Kind::Once,
             closure: Box::new(f)
         }
     }
 }
 
-pub struct EventEmitter<E, P> where E: Eq + Hash, P: Clone {
+/// Default capacity of the ring buffer backing a [`Subscriber`](struct.Subscriber.html)
+/// when none is given to [`EventEmitter::subscribe`](struct.EventEmitter.html#method.subscribe).
+pub const DEFAULT_RING_CAPACITY: usize = 16;
+
+/// Maps an event to the group it belongs to, so a listener can be registered
+/// against the whole group via
+/// [`EventEmitter::on_group`](struct.EventEmitter.html#method.on_group)
+/// instead of every concrete event in it.
+///
+/// Blanket-implemented as the trivial group `()` for every type, so the
+/// default [`EventEmitter<E, P>`](struct.EventEmitter.html) (with `G` left at
+/// its default) works without opting in to grouping.
+pub trait Grouped<G> {
+    fn group(&self) -> G;
+}
+
+impl<T: ?Sized> Grouped<()> for T {
+    fn group(&self) { }
+}
+
+pub struct EventEmitter<E, P, G = ()> where E: Eq + Hash, P: Clone, G: Eq + Hash {
     next_id: Id,
-    map: HashMap<E, Vec<Listener<P>>>
+    map: HashMap<E, Vec<Listener<P>>>,
+    subscribers: HashMap<E, Vec<SubscriberEntry<P>>>,
+    groups: HashMap<G, Vec<Listener<P>>>,
+    scoped: HashMap<E, Vec<ScopedEntry<P>>>
 }
 
-impl<E, P> EventEmitter<E, P> where E: Eq + Hash, P: Clone {
+impl<E, P> EventEmitter<E, P, ()> where E: Eq + Hash, P: Clone {
     pub fn new() -> Self {
+        Self::new_grouped()
+    }
+}
+
+impl<E, P, G> EventEmitter<E, P, G> where E: Eq + Hash, P: Clone, G: Eq + Hash {
+    /// Like [`new`](#method.new), but for an emitter whose group type `G` is
+    /// something other than the default `()`. Needed whenever
+    /// [`on_group`](#method.on_group) is used with a custom group type, since
+    /// plain `new` cannot infer it.
+    pub fn new_grouped() -> Self {
         Self {
             next_id: Id::default(),
-            map: HashMap::new()
+            map: HashMap::new(),
+            subscribers: HashMap::new(),
+            groups: HashMap::new(),
+            scoped: HashMap::new()
         }
     }
 
+    /// Registers a listener scoped to the returned [`Subscription`](struct.Subscription.html)
+    /// guard instead of an `Id`: dropping the guard is equivalent to `off`, without having to
+    /// keep the `Id` around. Internally the listener is held behind a `Weak` reference, so a
+    /// dropped guard's slot is simply skipped and reaped the next time `on_scoped` or `emit`
+    /// touches this event, rather than actively searched for and removed.
+    pub fn on_scoped<F>(&mut self, event: E, listener: F) -> Subscription<P> where F: Fn(P) + 'static {
+        let closure: Rc<Fn(P)> = Rc::new(listener);
+
+        let entries = self.scoped.entry(event).or_insert(Vec::new());
+        entries.retain(|entry| entry.closure.upgrade().is_some());
+        entries.push(ScopedEntry { closure: Rc::downgrade(&closure) });
+
+        Subscription { closure }
+    }
+
     pub fn on<F>(&mut self, event: E, listener: F) -> Id where F: Fn(P) + 'static {
         let id = self.next_id;
         let listeners = self.map.entry(event).or_insert(Vec::new());
@@ -47,6 +225,51 @@ impl<E, P> EventEmitter<E, P> where E: Eq + Hash, P: Clone {
         id
     }
 
+    /// Registers a listener that fires exactly once and is then removed
+    /// automatically. The returned `Id` can still be used with
+    /// [`off`](#method.off) to cancel it before it ever fires.
+    pub fn once<F>(&mut self, event: E, listener: F) -> Id where F: Fn(P) + 'static {
+        let id = self.next_id;
+        let listeners = self.map.entry(event).or_insert(Vec::new());
+
+        listeners.push(Listener::once(self.next_id, listener));
+
+        self.next_id += 1;
+        id
+    }
+
+    /// Registers a pull-based [`Subscriber`](struct.Subscriber.html) for `event`, backed by a
+    /// ring buffer of [`DEFAULT_RING_CAPACITY`](constant.DEFAULT_RING_CAPACITY.html) events.
+    pub fn subscribe(&mut self, event: E) -> Subscriber<P> {
+        self.subscribe_with_capacity(event, DEFAULT_RING_CAPACITY)
+    }
+
+    /// Like [`subscribe`](#method.subscribe), but with an explicit ring buffer capacity.
+    pub fn subscribe_with_capacity(&mut self, event: E, capacity: usize) -> Subscriber<P> {
+        let id = self.next_id;
+        let ring = Rc::new(RefCell::new(RingBuffer::new(capacity)));
+
+        let entries = self.subscribers.entry(event).or_insert(Vec::new());
+        entries.push(SubscriberEntry { id, ring: Rc::downgrade(&ring) });
+
+        self.next_id += 1;
+        Subscriber { read: 0, ring }
+    }
+
+    /// Registers a listener against `group` instead of a single event. It
+    /// fires for every emitted event whose [`Grouped::group`](trait.Grouped.html#tymethod.group)
+    /// equals `group`, in addition to any listener registered for that exact event via
+    /// [`on`](#method.on).
+    pub fn on_group<F>(&mut self, group: G, listener: F) -> Id where F: Fn(P) + 'static {
+        let id = self.next_id;
+        let listeners = self.groups.entry(group).or_insert(Vec::new());
+
+        listeners.push(Listener::new(self.next_id, listener));
+
+        self.next_id += 1;
+        id
+    }
+
     pub fn off(&mut self, id: Id) -> Result<(), Error> {
         for (_, listeners) in self.map.iter_mut() {
             let position = listeners.iter().position(|x| x.id == id);
@@ -57,17 +280,83 @@ impl<E, P> EventEmitter<E, P> where E: Eq + Hash, P: Clone {
             }
         }
 
+        for (_, entries) in self.subscribers.iter_mut() {
+            let position = entries.iter().position(|x| x.id == id);
+
+            if let Some(idx) = position {
+                entries.remove(idx);
+                return Ok(());
+            }
+        }
+
+        for (_, listeners) in self.groups.iter_mut() {
+            let position = listeners.iter().position(|x| x.id == id);
+
+            if let Some(idx) = position {
+                listeners.remove(idx);
+                return Ok(());
+            }
+        }
+
         Err(Error::ListenerNotFound)
     }
 
-    pub fn emit<B>(&self, event: &B, payload: P) -> Result<(), Error>
-    where E: Borrow<B>, B: ?Sized + Hash + Eq {
-        match self.map.get(event) {
+    pub fn emit<B>(&mut self, event: &B, payload: P) -> Result<(), Error>
+    where E: Borrow<B>, B: ?Sized + Hash + Eq + Grouped<G> {
+        let had_listeners = match self.map.get_mut(event) {
             Some(listeners) => {
                 listeners.iter().for_each(|f| (f.closure)(payload.clone()));
-                Ok(())
+                listeners.retain(|f| match f.kind {
+                    Kind::Normal => true,
+                    Kind::Once => false
+                });
+                true
+            },
+            None => false
+        };
+
+        let had_subscribers = match self.subscribers.get_mut(event) {
+            Some(entries) => {
+                entries.retain(|entry| entry.ring.upgrade().is_some());
+                entries.iter().for_each(|entry| {
+                    if let Some(ring) = entry.ring.upgrade() {
+                        ring.borrow_mut().push(payload.clone());
+                    }
+                });
+                true
             },
-            None => Err(Error::UnknownEvent)
+            None => false
+        };
+
+        let had_group_listeners = match self.groups.get_mut(&event.group()) {
+            Some(listeners) => {
+                listeners.iter().for_each(|f| (f.closure)(payload.clone()));
+                listeners.retain(|f| match f.kind {
+                    Kind::Normal => true,
+                    Kind::Once => false
+                });
+                true
+            },
+            None => false
+        };
+
+        let had_scoped_listeners = match self.scoped.get_mut(event) {
+            Some(entries) => {
+                entries.retain(|entry| entry.closure.upgrade().is_some());
+                entries.iter().for_each(|entry| {
+                    if let Some(closure) = entry.closure.upgrade() {
+                        (closure)(payload.clone());
+                    }
+                });
+                true
+            },
+            None => false
+        };
+
+        if had_listeners || had_subscribers || had_group_listeners || had_scoped_listeners {
+            Ok(())
+        } else {
+            Err(Error::UnknownEvent)
         }
     }
 }
@@ -149,4 +438,174 @@ mod tests {
         assert_eq!(rx.recv(), Ok(&1));
         assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
     }
+
+    #[test]
+    fn can_subscribe_and_pull_events() {
+        let mut emitter = EventEmitter::new();
+        let mut subscriber = emitter.subscribe("event");
+
+        emitter.emit("event", 1).unwrap();
+        emitter.emit("event", 2).unwrap();
+
+        assert_eq!(subscriber.try_recv(), Ok(1));
+        assert_eq!(subscriber.try_recv(), Ok(2));
+        assert_eq!(subscriber.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    #[test]
+    fn subscriber_iterates_over_buffered_events() {
+        let mut emitter = EventEmitter::new();
+        let subscriber = emitter.subscribe("event");
+
+        emitter.emit("event", 1).unwrap();
+        emitter.emit("event", 2).unwrap();
+        emitter.emit("event", 3).unwrap();
+
+        assert_eq!(subscriber.collect::<Vec<_>>(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn subscriber_reports_lag_when_it_falls_behind() {
+        let mut emitter = EventEmitter::new();
+        let mut subscriber = emitter.subscribe_with_capacity("event", 2);
+
+        for i in 0..5 {
+            emitter.emit("event", i).unwrap();
+        }
+
+        assert_eq!(subscriber.try_recv(), Err(TryRecvError::Lagged(3)));
+        assert_eq!(subscriber.try_recv(), Ok(3));
+        assert_eq!(subscriber.try_recv(), Ok(4));
+        assert_eq!(subscriber.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    #[test]
+    fn zero_capacity_subscriber_does_not_panic() {
+        let mut emitter = EventEmitter::new();
+        let mut subscriber = emitter.subscribe_with_capacity("event", 0);
+
+        emitter.emit("event", 1).unwrap();
+        emitter.emit("event", 2).unwrap();
+
+        assert_eq!(subscriber.try_recv(), Err(TryRecvError::Lagged(1)));
+        assert_eq!(subscriber.try_recv(), Ok(2));
+        assert_eq!(subscriber.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    #[test]
+    fn dropping_a_subscriber_reaps_it_on_next_emit() {
+        let mut emitter = EventEmitter::new();
+        let subscriber = emitter.subscribe("event");
+
+        assert_eq!(emitter.subscribers.get("event").unwrap().len(), 1);
+
+        drop(subscriber);
+        emitter.emit("event", 1).unwrap();
+
+        assert_eq!(emitter.subscribers.get("event").unwrap().len(), 0);
+    }
+
+    #[test]
+    fn once_listeners_fire_only_once() {
+        use std::sync::mpsc::{channel, TryRecvError};
+
+        let (sx, rx) = channel();
+        let sx1 = sx.clone();
+
+        let mut emitter = EventEmitter::new();
+        emitter.once("event", move |payload: i32| sx1.send(payload).unwrap());
+
+        emitter.emit("event", 1).unwrap();
+        emitter.emit("event", 2).unwrap();
+
+        assert_eq!(rx.recv(), Ok(1));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+        assert_eq!(emitter.map.get("event").unwrap().len(), 0);
+    }
+
+    #[test]
+    fn once_listener_can_be_cancelled_before_it_fires() {
+        let mut emitter = EventEmitter::new();
+        let id = emitter.once("event", |_: i32| panic!("should never fire"));
+
+        assert!(emitter.off(id).is_ok());
+
+        emitter.emit("event", 1).unwrap();
+    }
+
+    #[derive(PartialEq, Eq, Hash)]
+    enum ConnEvent {
+        Connected,
+        Disconnected,
+        Other
+    }
+
+    impl Grouped<&'static str> for ConnEvent {
+        fn group(&self) -> &'static str {
+            match *self {
+                ConnEvent::Connected | ConnEvent::Disconnected => "connection",
+                ConnEvent::Other => "other"
+            }
+        }
+    }
+
+    #[test]
+    fn group_listeners_fire_for_every_event_in_the_group() {
+        use std::sync::mpsc::{channel, TryRecvError};
+
+        let (sx, rx) = channel();
+
+        let mut emitter = EventEmitter::<ConnEvent, i32, &'static str>::new_grouped();
+        emitter.on_group("connection", move |payload: i32| sx.send(payload).unwrap());
+
+        emitter.emit(&ConnEvent::Connected, 1).unwrap();
+        emitter.emit(&ConnEvent::Disconnected, 2).unwrap();
+
+        // no listener is registered for the "other" group, so this is unmatched
+        assert!(emitter.emit(&ConnEvent::Other, 3).is_err());
+
+        assert_eq!(rx.recv(), Ok(1));
+        assert_eq!(rx.recv(), Ok(2));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    #[test]
+    fn off_removes_a_group_listener() {
+        let mut emitter = EventEmitter::<ConnEvent, i32, &'static str>::new_grouped();
+        let id = emitter.on_group("connection", |_: i32| panic!("should never fire"));
+
+        assert!(emitter.off(id).is_ok());
+
+        // the listener is gone, so nothing should fire (and nothing should panic)
+        emitter.emit(&ConnEvent::Connected, 1).unwrap();
+    }
+
+    #[test]
+    fn scoped_listener_fires_while_its_guard_is_alive() {
+        use std::sync::mpsc::{channel, TryRecvError};
+
+        let (sx, rx) = channel();
+
+        let mut emitter = EventEmitter::new();
+        let _subscription = emitter.on_scoped("event", move |payload: i32| sx.send(payload).unwrap());
+
+        emitter.emit("event", 1).unwrap();
+
+        assert_eq!(rx.recv(), Ok(1));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    #[test]
+    fn dropping_a_subscription_reaps_it_on_next_emit() {
+        let mut emitter = EventEmitter::new();
+        let subscription = emitter.on_scoped("event", |_: i32| panic!("should never fire"));
+
+        assert_eq!(emitter.scoped.get("event").unwrap().len(), 1);
+
+        drop(subscription);
+
+        // the guard is gone, so nothing should fire (and nothing should panic)
+        emitter.emit("event", 1).unwrap();
+        assert_eq!(emitter.scoped.get("event").unwrap().len(), 0);
+    }
 }